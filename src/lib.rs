@@ -1,19 +1,34 @@
 //! This is a derive procedural macro that will let you add custom derive
-//! and attributes over structs, enums and unions. This derive will add two impl on the
-//! type. The `as_string()` method returns a json serialized string representation of the type
-//! with any meta information annotated with `structype_meta("key"=val)` attribute,
-//! while the `print_fields()` method will print the same to STDOUT.
-//! This macro will panic at compile time if annotated over tuple and unit structs.
+//! and attributes over structs, enums and unions. This derive implements the `StrucType`
+//! trait (re-exported from the `structype` crate) on the annotated type. Its `as_string()`
+//! method returns a json serialized string representation of the type with any meta
+//! information annotated with `structype_meta("key"=val)` attribute, while the
+//! `print_fields()` method prints the same to STDOUT; both are default trait methods built
+//! on top of `schema()`, which returns the structured `TypeMapVec` directly.
+//! Structs, tuple structs, unit structs, enums (including variants carrying data) and unions
+//! are all supported; a field's positional index ("0", "1", ...) is used as its `field_name`
+//! for tuple structs and tuple-like enum variants, and a unit struct's single record uses the
+//! struct's own name.
+//!
+//! A top-level `structype_meta(...)` attribute on the type itself attaches schema-level
+//! metadata (e.g. a table name or a version) rather than field-level metadata; when present,
+//! `as_string()`/`print_fields()` wrap the field records in an object carrying `type_name`,
+//! `type_meta` and `fields`, while `schema()` keeps returning the flat field `Vec<TypeMap>`.
+//! Ordinary doc comments and common derive-adjacent attributes (`#[allow(..)]`, `#[cfg(..)]`,
+//! `#[cfg_attr(..)]`, `#[repr(..)]`, other `#[derive(..)]`s) are left alone; any other
+//! unrecognized top-level attribute is rejected with a compile error.
 //!
 //! # Example:
 //! ```
+//! use structype::StrucType;
 //! use structype_derive::StrucType;
 //! #[derive(StrucType)]
-//! // #[structype_meta("labelover_ride=name")] This will panic the macro
+//! #[structype_meta(table="users", version=2)]
+//! // #[structype_meta("labelover_ride=name")] This will fail to compile
 //! struct UserStruct {
-//!     #[structype_meta(override_name="Primary ID", order="1")]
+//!     #[structype_meta(override_name="Primary ID", order=1, primary=true)]
 //!     id: i64,
-//!     #[structype_meta(override_name="name", order="0")]
+//!     #[structype_meta(override_name="name", order=0)]
 //!     username: String,
 //!     org: String,
 //!     details: Details,
@@ -32,282 +47,472 @@
 //!     let data = Details::as_string();
 //!     println!("{}", data);
 //! }
+//!
+//! // Being a trait, `StrucType` can also be used as a generic bound:
+//! fn print_via_trait<T: StrucType>() {
+//!     T::print_fields();
+//! }
 //! ```
 //! The above will generate and return a json serialized string representation where the key is
-//! the struct's field name and the value is a `HashMap<String, String>` of `structype_meta`'s key-val. If the `structype_meta` is
+//! `field_name` for each record's field name (next to its `ty` and `children`) and `meta`
+//! is a `HashMap<String, Value>` of `structype_meta`'s
+//! key-val, where `Value` is a `serde_json::Value` holding whatever literal kind (string,
+//! integer, float, or bool) was written in the attribute. If the `structype_meta` is
 //! absent, the field's associated value would be an empty `{}`.
 //!
 //! # Output:
 //! ```json
-//! [
-//!     {
-//!         "field_name": "id",
-//!         "meta": {
-//!             "order": "1",
-//!             "override_name": "Primary ID"
-//!         }
+//! {
+//!     "type_name": "UserStruct",
+//!     "type_meta": {
+//!         "table": "users",
+//!         "version": 2
 //!     },
-//!     {
-//!         "field_name": "username",
-//!         "meta": {
-//!             "override_name": "name",
-//!             "order": "0"
+//!     "fields": [
+//!         {
+//!             "field_name": "id",
+//!             "ty": "i64",
+//!             "meta": {
+//!                 "order": 1,
+//!                 "primary": true,
+//!                 "override_name": "Primary ID"
+//!             },
+//!             "children": null
+//!         },
+//!         {
+//!             "field_name": "username",
+//!             "ty": "String",
+//!             "meta": {
+//!                 "override_name": "name",
+//!                 "order": 0
+//!             },
+//!             "children": null
+//!         },
+//!         {
+//!             "field_name": "org",
+//!             "ty": "String",
+//!             "meta": {},
+//!             "children": null
+//!         },
+//!         {
+//!             "field_name": "details",
+//!             "ty": "Details",
+//!             "meta": {},
+//!             "children": [
+//!                 {
+//!                     "field_name": "user_attributes",
+//!                     "ty": "std::collections::HashMap<String, String>",
+//!                     "meta": {},
+//!                     "children": null
+//!                 }
+//!             ]
 //!         }
-//!     },
-//!     {
-//!         "field_name": "org",
-//!         "meta": {}
-//!     },
-//!     {
-//!         "field_name": "details",
-//!         "meta": {}
-//!     }
-//! ]
+//!     ]
+//! }
 //! ```
-//! 
+//! `Details` carries no top-level `structype_meta`, so its own `as_string()` stays a plain
+//! field array rather than this wrapped form.
+//!
 //! If this serialized string needs to be deserialized into a struct, use the same type used here
 //!
 //! cargo.toml:
 //! ```toml
-//! structype = "3.0.0"
+//! structype = "4.0.0"
 //! ```
-//! 
+//!
 //! your code:
 //! ```rust
-//! use structype::typeMapVec;
+//! use structype::{StrucType, TypeMapVec};
 //!```
+//! `schema()` returns the `Vec<TypeMap>` directly, so code that needs the structured data
+//! rather than its JSON rendering does not need to re-parse `as_string()`'s output.
+//!
+//! # Nested types
 //!
+//! If a field's type is itself a type that derives `StrucType` (i.e. it is not one of the
+//! recognized primitive/std types), the generated record for that field carries the nested
+//! type's own field records under `children`, so the description of `UserStruct` above shows
+//! `Details`'s fields inline under the `details` record instead of as an opaque leaf.
 //!
 
 
 use proc_macro::{self, TokenStream};
-use structype::{TypeMap, TypeMapVec};
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use std::collections::HashMap;
-use syn::{parse_macro_input, DataEnum, DataUnion, DeriveInput, FieldsNamed};
+use syn::{parse_macro_input, DataEnum, DataUnion, DeriveInput, FieldsNamed, Type};
 
-#[proc_macro_derive(StrucType, attributes(structype_meta))]
-pub fn structmap(input: TokenStream) -> TokenStream {
-    let ast: DeriveInput = parse_macro_input!(input);
-    let name = &ast.ident;
-    let top_attr = &ast.attrs;
-    for attr in top_attr.iter() {
-        let meta = attr.parse_meta();
-        match meta {
-            _ => panic!("Cannot apply attribute outside a type. Applicable only inside the type on type fields."),
-        }
+/// Folds `err` into `slot`, combining with whatever error (if any) is already there so the
+/// caller can report every problem in one `compile_error!` instead of stopping at the first.
+fn push_error(slot: &mut Option<syn::Error>, err: syn::Error) {
+    match slot {
+        Some(existing) => existing.combine(err),
+        None => *slot = Some(err),
     }
+}
 
-    let description = match &ast.data {
-        syn::Data::Struct(s) => {
-            match &s.fields {
-                syn::Fields::Named(FieldsNamed { named, .. }) => {
-                    let mut structype_map: TypeMapVec = Vec::new();
-                    let iters = named.iter().map(|f| (&f.ident, &f.attrs));
-                    for (if_ident, attrs) in iters {
-                        if let Some(ident) = if_ident {
-                            if attrs.len() > 0 {
-                                let mut record = TypeMap {
-                                    field_name: ident.to_string(),
-                                    meta: HashMap::new(),
-                                };
-                                for attr in attrs.iter() {
-                                    let meta = attr.parse_meta().unwrap();
-                                    match meta {
-                                        syn::Meta::List(metalist) => {
-                                            let pairs = metalist
-                                                .nested
-                                                .into_pairs()
-                                                .map(|pair| pair.into_value());
-                                            for pair in pairs {
-                                                match pair {
-                                                syn::NestedMeta::Meta(meta) => match meta {
-                                                    syn::Meta::Path(_) => {panic!(r#"invalid. Use the format structype_meta(label="foo", ord="10")"#)}
-                                                    syn::Meta::List(_) => {panic!(r#"invalid. Use the format structype_meta(label="foo", ord="10")"#)}
-                                                    syn::Meta::NameValue(meta_nameval) => {
-                                                        let path = meta_nameval.path;
-                                                        match meta_nameval.lit {
-                                                            syn::Lit::Str(str_lit) => {
+/// Types we treat as leaves rather than something else deriving `StrucType`.
+///
+/// This is a best-effort check on the textual type path: anything not recognized here is
+/// assumed to be a user type and is recursed into via its own generated `schema()`.
+fn is_primitive_like(ty: &Type) -> bool {
+    const LEAF_TYPES: &[&str] = &[
+        "bool", "char", "str", "String",
+        "i8", "i16", "i32", "i64", "i128", "isize",
+        "u8", "u16", "u32", "u64", "u128", "usize",
+        "f32", "f64",
+        "Vec", "Option", "Box", "HashMap", "BTreeMap", "HashSet", "BTreeSet",
+        "Arc", "Rc", "Cow", "Mutex", "RwLock", "Cell", "RefCell",
+    ];
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| LEAF_TYPES.contains(&segment.ident.to_string().as_str()))
+            .unwrap_or(false),
+        _ => true,
+    }
+}
 
-                                                                record.meta.insert(path.get_ident().unwrap().to_string(), str_lit.value());
-                                                            }
-                                                            _ => {panic!("Only string type is supported now")}
-                                                        }
-                                                    }
-                                                }
-                                                syn::NestedMeta::Lit(_) => {panic!("Lit is not applicable. Annotate as key-value")}
-                                            }
-                                            }
-                                            structype_map.push(record.clone());
-                                        }
+/// Normalizes the token-stream rendering of a type (e.g. `quote!(#ty).to_string()`) by
+/// collapsing the extra whitespace `quote` inserts around generics, paths, and qualified
+/// segments, so `std :: collections :: HashMap < String , String >` becomes
+/// `std::collections::HashMap<String, String>`.
+fn normalize_type_string(raw: String) -> String {
+    raw.replace(" < ", "<")
+        .replace(" > ", ">")
+        .replace(" >", ">")
+        .replace(" ,", ",")
+        .replace(" :: ", "::")
+}
 
-                                        _ => panic!(
-                                            r#"Not applicable. Present a list of key-value attributes like structype_meta(label="foo", ord="10")"#
-                                        ),
-                                        // syn::Meta::Path(_) => {}
+/// Parses a `structype_meta(key = val, ..)` attribute list into parallel key/value vectors,
+/// where each value is the tokens for a `serde_json::Value` built from the literal's own kind
+/// (string, integer, float, or bool). Every malformed attribute found is collected and
+/// combined into a single spanned error rather than bailing out on the first one. Attributes
+/// other than `structype_meta` (doc comments, `#[allow(..)]`, another derive's own helper
+/// attribute, ...) are not ours to interpret and are silently left alone.
+fn parse_meta_pairs(attrs: &[syn::Attribute]) -> Result<(Vec<String>, Vec<TokenStream2>), syn::Error> {
+    let mut keys = Vec::new();
+    let mut vals = Vec::new();
+    let mut error: Option<syn::Error> = None;
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("structype_meta")) {
+        let meta = match attr.parse_meta() {
+            Ok(meta) => meta,
+            Err(e) => {
+                push_error(&mut error, e);
+                continue;
+            }
+        };
+        match meta.clone() {
+            syn::Meta::List(metalist) => {
+                for pair in metalist.nested.into_pairs().map(|p| p.into_value()) {
+                    match pair {
+                        syn::NestedMeta::Meta(nested_meta) => match nested_meta {
+                            syn::Meta::Path(path) => push_error(
+                                &mut error,
+                                syn::Error::new_spanned(
+                                    &path,
+                                    r#"invalid. Use the format structype_meta(label="foo", ord="10")"#,
+                                ),
+                            ),
+                            syn::Meta::List(list) => push_error(
+                                &mut error,
+                                syn::Error::new_spanned(
+                                    &list,
+                                    r#"invalid. Use the format structype_meta(label="foo", ord="10")"#,
+                                ),
+                            ),
+                            syn::Meta::NameValue(meta_nameval) => {
+                                let key = match meta_nameval.path.get_ident() {
+                                    Some(ident) => ident.to_string(),
+                                    None => {
+                                        push_error(
+                                            &mut error,
+                                            syn::Error::new_spanned(
+                                                &meta_nameval.path,
+                                                "invalid. Keys must be a single identifier, \
+                                                 e.g. structype_meta(label=\"foo\")",
+                                            ),
+                                        );
+                                        continue;
                                     }
-                                }
-                            } else {
-                                let val = TypeMap {
-                                    field_name: ident.to_string(),
-                                    meta: HashMap::new(),
                                 };
-                                structype_map.push(val);
+                                match meta_nameval.lit {
+                                    syn::Lit::Str(lit) => {
+                                        let value = lit.value();
+                                        keys.push(key);
+                                        vals.push(quote! { structype::serde_json::Value::from(#value) });
+                                    }
+                                    syn::Lit::Int(lit) => match lit.base10_parse::<i64>() {
+                                        Ok(value) => {
+                                            keys.push(key);
+                                            vals.push(
+                                                quote! { structype::serde_json::Value::from(#value) },
+                                            );
+                                        }
+                                        Err(e) => push_error(&mut error, e),
+                                    },
+                                    syn::Lit::Float(lit) => match lit.base10_parse::<f64>() {
+                                        Ok(value) => {
+                                            keys.push(key);
+                                            vals.push(
+                                                quote! { structype::serde_json::Value::from(#value) },
+                                            );
+                                        }
+                                        Err(e) => push_error(&mut error, e),
+                                    },
+                                    syn::Lit::Bool(lit) => {
+                                        let value = lit.value;
+                                        keys.push(key);
+                                        vals.push(quote! { structype::serde_json::Value::from(#value) });
+                                    }
+                                    other => push_error(
+                                        &mut error,
+                                        syn::Error::new_spanned(
+                                            &other,
+                                            "Only string, integer, float, or bool literals are supported",
+                                        ),
+                                    ),
+                                }
                             }
-                        }
+                        },
+                        syn::NestedMeta::Lit(lit) => push_error(
+                            &mut error,
+                            syn::Error::new_spanned(&lit, "Lit is not applicable. Annotate as key-value"),
+                        ),
                     }
-                    serde_json::to_string(&structype_map).unwrap()
                 }
-                syn::Fields::Unnamed(_) => panic!("Not applicable to Tuple structs"),
+            }
+            _ => push_error(
+                &mut error,
+                syn::Error::new_spanned(
+                    &meta,
+                    r#"Not applicable. Present a list of key-value attributes like structype_meta(label="foo", ord="10")"#,
+                ),
+            ),
+        }
+    }
+    match error {
+        Some(e) => Err(e),
+        None => Ok((keys, vals)),
+    }
+}
+
+/// Assembles the tokens for a single `structype::TypeMap` record from its already-resolved
+/// parts, to be embedded inside a generated `vec![ .. ]` expression.
+fn assemble_record(
+    field_name: String,
+    ty_string: String,
+    attrs: &[syn::Attribute],
+    children_tokens: TokenStream2,
+) -> Result<TokenStream2, syn::Error> {
+    let (keys, vals) = parse_meta_pairs(attrs)?;
+    Ok(quote! {
+        structype::TypeMap {
+            field_name: #field_name.to_string(),
+            ty: #ty_string.to_string(),
+            meta: {
+                #[allow(unused_mut)]
+                let mut m = ::std::collections::HashMap::new();
+                #(m.insert(#keys.to_string(), #vals);)*
+                m
+            },
+            children: #children_tokens,
+        }
+    })
+}
+
+/// Builds the tokens for a single `structype::TypeMap` record, to be embedded inside a
+/// generated `vec![ .. ]` expression. `ty` is `None` for record kinds that don't carry a
+/// single recursable Rust type; such a record gets an empty `ty` and `children: None`
+/// (records whose children come from elsewhere go through [`assemble_record`] directly).
+fn build_record_tokens(
+    field_name: String,
+    ty: Option<&Type>,
+    attrs: &[syn::Attribute],
+) -> Result<TokenStream2, syn::Error> {
+    let (ty_string, children_tokens) = match ty {
+        Some(ty) if is_primitive_like(ty) => (normalize_type_string(quote!(#ty).to_string()), quote! { None }),
+        Some(ty) => {
+            let ty_string = normalize_type_string(quote!(#ty).to_string());
+            (ty_string, quote! { Some(<#ty as structype::StrucType>::schema()) })
+        }
+        None => (String::new(), quote! { None }),
+    };
+    assemble_record(field_name, ty_string, attrs, children_tokens)
+}
 
-                syn::Fields::Unit => panic!("Not applicable to Unit structs"),
+/// Builds the flat list of `TypeMap` records for a tuple struct's (or a tuple-like enum
+/// variant's) unnamed fields, using the field's positional index ("0", "1", ...) as its
+/// `field_name` while still honoring any `structype_meta` on that field.
+fn build_unnamed_field_records(unnamed: &syn::FieldsUnnamed) -> Result<Vec<TokenStream2>, syn::Error> {
+    let mut records = Vec::new();
+    let mut error: Option<syn::Error> = None;
+    for (index, f) in unnamed.unnamed.iter().enumerate() {
+        match build_record_tokens(index.to_string(), Some(&f.ty), &f.attrs) {
+            Ok(tokens) => records.push(tokens),
+            Err(e) => push_error(&mut error, e),
+        }
+    }
+    match error {
+        Some(e) => Err(e),
+        None => Ok(records),
+    }
+}
+
+/// Describes an enum variant's payload (if any) as the `children` of its own record: `None`
+/// for a unit variant, or the flat field records of a named/tuple variant.
+fn build_variant_children(fields: &syn::Fields) -> Result<TokenStream2, syn::Error> {
+    match fields {
+        syn::Fields::Unit => Ok(quote! { None }),
+        syn::Fields::Named(FieldsNamed { named, .. }) => {
+            let mut field_records = Vec::new();
+            let mut error: Option<syn::Error> = None;
+            for f in named.iter() {
+                let ident = f.ident.as_ref().expect("named field without an identifier");
+                match build_record_tokens(ident.to_string(), Some(&f.ty), &f.attrs) {
+                    Ok(tokens) => field_records.push(tokens),
+                    Err(e) => push_error(&mut error, e),
+                }
+            }
+            match error {
+                Some(e) => Err(e),
+                None => Ok(quote! { Some(vec![ #(#field_records),* ]) }),
             }
         }
-        // Enums parsing starts here
-        syn::Data::Enum(DataEnum { variants, .. }) => {
-            let mut structype_map: TypeMapVec = Vec::new();
-            let iters = variants.iter().map(|f| (&f.ident, &f.attrs));
-            for (if_ident, attrs) in iters {
-                if attrs.len() > 0 {
-                    let mut record = TypeMap {
-                        field_name: if_ident.to_string(),
-                        meta: HashMap::new(),
-                    };
-                    for attr in attrs.iter() {
-                        let meta = attr.parse_meta().unwrap();
-                        match meta {
-                            syn::Meta::List(metalist) => {
-                                let pairs =
-                                    metalist.nested.into_pairs().map(|pair| pair.into_value());
-                                for pair in pairs {
-                                    match pair {
-                                        syn::NestedMeta::Meta(meta) => match meta {
-                                            syn::Meta::Path(_) => {
-                                                panic!(r#"invalid. Add as key="value#""#)
-                                            }
-                                            syn::Meta::List(_) => {
-                                                panic!(r#"invalid. Add as key="value#""#)
-                                            }
-                                            syn::Meta::NameValue(meta_nameval) => {
-                                                let path = meta_nameval.path;
-                                                match meta_nameval.lit {
-                                                    syn::Lit::Str(str_lit) => {
-                                                        record.meta.insert(
-                                                            path.get_ident().unwrap().to_string(),
-                                                            str_lit.value(),
-                                                        );
-                                                    }
-                                                    _ => {
-                                                        panic!("Only string type is supported now")
-                                                    }
-                                                }
-                                            }
-                                        },
-                                        syn::NestedMeta::Lit(_) => {
-                                            panic!("Lit is not applicable. Annotate as key-value")
-                                        }
-                                    }
-                                }
-                                structype_map.push(record.clone());
-                            }
+        syn::Fields::Unnamed(unnamed) => {
+            let field_records = build_unnamed_field_records(unnamed)?;
+            Ok(quote! { Some(vec![ #(#field_records),* ]) })
+        }
+    }
+}
 
-                            _ => panic!(
-                                r#"Not applicable. Present a list of key-value attributes like structype_meta(label="foo", ord="10")"#
-                            ),
-                            // syn::Meta::Path(_) => {}
-                        }
+/// Builds the tokens for an enum variant's own record, with its payload fields (if any)
+/// nested under `children` via [`build_variant_children`].
+fn build_variant_record(variant: &syn::Variant) -> Result<TokenStream2, syn::Error> {
+    let children_tokens = build_variant_children(&variant.fields)?;
+    assemble_record(variant.ident.to_string(), String::new(), &variant.attrs, children_tokens)
+}
+
+#[proc_macro_derive(StrucType, attributes(structype_meta))]
+pub fn structmap(input: TokenStream) -> TokenStream {
+    let ast: DeriveInput = parse_macro_input!(input);
+    let name = &ast.ident;
+
+    let mut error: Option<syn::Error> = None;
+    let mut type_meta_attrs: Vec<syn::Attribute> = Vec::new();
+    // Attributes the compiler or other, unrelated derives may legitimately leave on the type
+    // (doc comments desugar to `#[doc = "..."]`) that we should just ignore rather than reject.
+    const IGNORED_TOPLEVEL_ATTRS: &[&str] = &["doc", "allow", "cfg", "cfg_attr", "repr", "derive"];
+    for attr in ast.attrs.iter() {
+        if attr.path.is_ident("structype_meta") {
+            type_meta_attrs.push(attr.clone());
+        } else if IGNORED_TOPLEVEL_ATTRS
+            .iter()
+            .any(|ignored| attr.path.is_ident(ignored))
+        {
+            continue;
+        } else {
+            push_error(
+                &mut error,
+                syn::Error::new_spanned(
+                    attr,
+                    "Cannot apply attribute outside a type. Applicable only inside the type on type \
+                     fields, or as a top-level structype_meta(...) attribute.",
+                ),
+            );
+        }
+    }
+
+    let mut records: Vec<TokenStream2> = Vec::new();
+    match &ast.data {
+        syn::Data::Struct(s) => match &s.fields {
+            syn::Fields::Named(FieldsNamed { named, .. }) => {
+                for f in named.iter() {
+                    let ident = f.ident.as_ref().expect("named field without an identifier");
+                    match build_record_tokens(ident.to_string(), Some(&f.ty), &f.attrs) {
+                        Ok(tokens) => records.push(tokens),
+                        Err(e) => push_error(&mut error, e),
                     }
-                } else {
-                    let val = TypeMap {
-                        field_name: if_ident.to_string(),
-                        meta: HashMap::new(),
-                    };
-                    structype_map.push(val);
                 }
             }
-            serde_json::to_string(&structype_map).unwrap()
+            syn::Fields::Unnamed(fields) => match build_unnamed_field_records(fields) {
+                Ok(field_records) => records.extend(field_records),
+                Err(e) => push_error(&mut error, e),
+            },
+            syn::Fields::Unit => {
+                match assemble_record(name.to_string(), name.to_string(), &[], quote! { None }) {
+                    Ok(tokens) => records.push(tokens),
+                    Err(e) => push_error(&mut error, e),
+                }
+            }
+        },
+        // Enums parsing starts here
+        syn::Data::Enum(DataEnum { variants, .. }) => {
+            for v in variants.iter() {
+                match build_variant_record(v) {
+                    Ok(tokens) => records.push(tokens),
+                    Err(e) => push_error(&mut error, e),
+                }
+            }
         }
         syn::Data::Union(DataUnion {
             fields: FieldsNamed { named, .. },
             ..
         }) => {
-            let mut structype_map: TypeMapVec = Vec::new();
-            let iters = named.iter().map(|f| (&f.ident, &f.attrs));
-            for (if_ident, attrs) in iters {
-                if let Some(ident) = if_ident {
-                    if attrs.len() > 0 {
-                        let mut record = TypeMap {
-                            field_name: ident.to_string(),
-                            meta: HashMap::new(),
-                        };
-                        for attr in attrs.iter() {
-                            let meta = attr.parse_meta().unwrap();
-                            match meta {
-                                syn::Meta::List(metalist) => {
-                                    let pairs =
-                                        metalist.nested.into_pairs().map(|pair| pair.into_value());
-                                    for pair in pairs {
-                                        match pair {
-                                            syn::NestedMeta::Meta(meta) => match meta {
-                                                syn::Meta::Path(_) => {
-                                                    panic!(r#"invalid. Use the format structype_meta(label="foo", ord="10")"#)
-                                                }
-                                                syn::Meta::List(_) => {
-                                                    panic!(r#"invalid. Use the format structype_meta(label="foo", ord="10")"#)
-                                                }
-                                                syn::Meta::NameValue(meta_nameval) => {
-                                                    let path = meta_nameval.path;
-                                                    match meta_nameval.lit {
-                                                        syn::Lit::Str(str_lit) => {
-                                                            record.meta.insert(
-                                                                path.get_ident()
-                                                                    .unwrap()
-                                                                    .to_string(),
-                                                                str_lit.value(),
-                                                            );
-                                                        }
-                                                        _ => panic!(
-                                                            "Only string type is supported now"
-                                                        ),
-                                                    }
-                                                }
-                                            },
-                                            syn::NestedMeta::Lit(_) => panic!(
-                                                r#"Literal is not applicable. Annotate as key-value like structype_meta(label="foo#", ord="10")"#
-                                            ),
-                                        }
-                                    }
-                                    structype_map.push(record.clone());
-                                }
-
-                                _ => panic!(
-                                    r#"Not applicable. Present a list of key-value attributes like structype_meta(label="foo", ord="10")"#
-                                ),
-                            }
-                        }
-                    } else {
-                        let val = TypeMap {
-                            field_name: ident.to_string(),
-                            meta: HashMap::new(),
-                        };
-                        structype_map.push(val);
-                    }
+            for f in named.iter() {
+                let ident = f.ident.as_ref().expect("named field without an identifier");
+                match build_record_tokens(ident.to_string(), Some(&f.ty), &f.attrs) {
+                    Ok(tokens) => records.push(tokens),
+                    Err(e) => push_error(&mut error, e),
                 }
             }
-            serde_json::to_string(&structype_map).unwrap()
         }
-    };
+    }
 
-    let output = quote! {
-    impl #name {
-        pub fn print_fields() {
-        println!("{}", #description);
+    let type_meta = match parse_meta_pairs(&type_meta_attrs) {
+        Ok(type_meta) => Some(type_meta),
+        Err(e) => {
+            push_error(&mut error, e);
+            None
         }
+    };
 
-        pub fn as_string() -> String {
-            return #description.to_string()
-        }
+    if let Some(error) = error {
+        return error.to_compile_error().into();
     }
+
+    let output = match type_meta {
+        Some((type_keys, type_vals)) if !type_keys.is_empty() => {
+            let type_name_str = name.to_string();
+            quote! {
+            impl structype::StrucType for #name {
+                fn schema() -> structype::TypeMapVec {
+                    vec![ #(#records),* ]
+                }
+
+                fn as_string() -> String {
+                    #[allow(unused_mut)]
+                    let mut type_meta = ::std::collections::HashMap::new();
+                    #(type_meta.insert(#type_keys.to_string(), #type_vals);)*
+                    structype::serde_json::json!({
+                        "type_name": #type_name_str,
+                        "type_meta": type_meta,
+                        "fields": Self::schema(),
+                    })
+                    .to_string()
+                }
+            }
+            }
+        }
+        _ => quote! {
+            impl structype::StrucType for #name {
+                fn schema() -> structype::TypeMapVec {
+                    vec![ #(#records),* ]
+                }
+            }
+        },
     };
 
     output.into()
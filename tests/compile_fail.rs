@@ -0,0 +1,8 @@
+// Asserts that the invalid-usage compile_error! diagnostics added for malformed
+// structype_meta attributes and unknown top-level attributes actually fire, instead of
+// relying on manual inspection.
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/fail/*.rs");
+}
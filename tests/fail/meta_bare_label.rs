@@ -0,0 +1,10 @@
+use structype_derive::StrucType;
+
+// A bare path (no `= value`) inside structype_meta is rejected.
+#[derive(StrucType)]
+struct BareLabel {
+    #[structype_meta(primary)]
+    id: i64,
+}
+
+fn main() {}
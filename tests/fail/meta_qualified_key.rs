@@ -0,0 +1,11 @@
+use structype_derive::StrucType;
+
+// A multi-segment key is syntactically valid `Meta::NameValue` but not a supported
+// structype_meta key; this used to panic the macro instead of emitting a compile_error!.
+#[derive(StrucType)]
+struct QualifiedKey {
+    #[structype_meta(a::b = "x")]
+    id: i64,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use structype_derive::StrucType;
+
+// A top-level attribute other than structype_meta is rejected.
+#[derive(StrucType)]
+#[some_unrelated_attribute]
+struct UnknownAttr {
+    id: i64,
+}
+
+fn main() {}
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
+use structype::StrucType;
 use structype_derive::StrucType;
+
 #[derive(StrucType)]
-// #[structype_meta("labelover_ride=name")] // This will panic the macro
+// #[structype_meta("labelover_ride=name")] // This will fail to compile
 struct UserStruct {
     #[structype_meta(override_name = "Primary ID", order = "1")]
     _id: i64,
@@ -30,7 +32,8 @@ union MyUnion {
     _signed: i32,
 }
 
-fn main() {
+#[test]
+fn struct_enum_and_union_expand_and_print() {
     UserStruct::print_fields();
     let data = UserStruct::as_string();
     println!("{}", data);
@@ -45,3 +48,95 @@ fn main() {
     let data = MyUnion::as_string();
     println!("{}", data);
 }
+
+// Typed (non-string) structype_meta values: integer, float, and bool literals.
+#[derive(StrucType)]
+struct TypedMeta {
+    #[structype_meta(order = 1, weight = 0.5, primary = true)]
+    _id: i64,
+    _name: String,
+}
+
+#[test]
+fn typed_meta_values_serialize_as_json_numbers_and_bools() {
+    let schema = TypedMeta::schema();
+    let id_meta = &schema[0].meta;
+    assert_eq!(id_meta["order"], serde_json::json!(1));
+    assert_eq!(id_meta["weight"], serde_json::json!(0.5));
+    assert_eq!(id_meta["primary"], serde_json::json!(true));
+}
+
+// Tuple struct: unnamed fields are keyed by their positional index.
+#[derive(StrucType)]
+#[allow(dead_code)]
+struct Point(#[structype_meta(order = 0)] i64, i64);
+
+// Unit struct: its single record uses the struct's own name.
+#[derive(StrucType)]
+struct Marker;
+
+// Enum with unit, tuple-like, and named-field variants carrying data.
+#[derive(StrucType)]
+enum Shape {
+    _Unit,
+    _Circle(f64),
+    _Rectangle { _width: f64, _height: f64 },
+}
+
+#[test]
+fn tuple_struct_fields_are_keyed_by_position() {
+    let schema = Point::schema();
+    assert_eq!(schema[0].field_name, "0");
+    assert_eq!(schema[0].ty, "i64");
+    assert_eq!(schema[0].meta["order"], serde_json::json!(0));
+    assert_eq!(schema[1].field_name, "1");
+    assert_eq!(schema[1].ty, "i64");
+}
+
+#[test]
+fn unit_struct_record_uses_the_struct_name() {
+    let schema = Marker::schema();
+    assert_eq!(schema.len(), 1);
+    assert_eq!(schema[0].field_name, "Marker");
+    assert_eq!(schema[0].ty, "Marker");
+}
+
+#[test]
+fn enum_variants_carry_their_payload_as_children() {
+    let schema = Shape::schema();
+
+    let unit = schema.iter().find(|r| r.field_name == "_Unit").unwrap();
+    assert!(unit.children.is_none());
+
+    let circle = schema.iter().find(|r| r.field_name == "_Circle").unwrap();
+    let circle_children = circle.children.as_ref().unwrap();
+    assert_eq!(circle_children[0].field_name, "0");
+    assert_eq!(circle_children[0].ty, "f64");
+
+    let rectangle = schema
+        .iter()
+        .find(|r| r.field_name == "_Rectangle")
+        .unwrap();
+    let rectangle_children = rectangle.children.as_ref().unwrap();
+    assert_eq!(rectangle_children[0].field_name, "_width");
+    assert_eq!(rectangle_children[1].field_name, "_height");
+}
+
+/// A top-level structype_meta(...) attribute attaches schema-level metadata; the doc
+/// comment on this struct also exercises that ordinary `#[doc = "..."]` attributes are left
+/// alone rather than rejected as unknown top-level attributes.
+#[derive(StrucType)]
+#[structype_meta(table = "users", version = 2)]
+struct Account {
+    _id: i64,
+}
+
+#[test]
+fn toplevel_structype_meta_wraps_as_string_with_type_name_and_type_meta() {
+    let data = Account::as_string();
+    let value: serde_json::Value = serde_json::from_str(&data).expect("as_string returns valid json");
+    assert_eq!(value["type_name"], "Account");
+    assert_eq!(value["type_meta"]["table"], "users");
+    assert_eq!(value["type_meta"]["version"], 2);
+    assert_eq!(value["fields"], serde_json::json!(Account::schema()));
+}